@@ -8,16 +8,22 @@ use contracts::rollups_facet::*;
 use ethers::{
     prelude::EthEvent,
     providers::Middleware,
-    types::{Address, U256},
+    types::{Address, H256, U256},
 };
 use state_fold::{
     utils as fold_utils, FoldMiddleware, Foldable, StateFoldEnvironment,
     SyncMiddleware,
 };
+use serde::{Deserialize, Serialize};
 use state_fold_types::{ethers, Block};
-use std::sync::Arc;
+use std::collections::{HashMap, VecDeque};
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
-#[derive(Clone, Debug)]
+use lru::LruCache;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum ContractPhase {
     InputAccumulation {},
     AwaitingConsensus {
@@ -29,10 +35,285 @@ pub enum ContractPhase {
     },
 }
 
+/// A phase-change transition observed at the chain tip, tagged with the
+/// block that produced it so it can be walked forward (to promote it once
+/// buried deep enough) or dropped (if that block gets reorged out).
+///
+/// `phase` is `Arc`-wrapped so that carrying a transition through the
+/// pending queue (and cloning that queue on every block the rolling
+/// finality checker re-evaluates) is a refcount bump rather than a deep
+/// clone of whatever claims data the phase happens to be carrying.
+#[derive(Clone, Debug)]
+pub struct PendingTransition {
+    pub phase: Arc<ContractPhase>,
+    pub block_number: U256,
+    pub block_hash: H256,
+    pub timestamp: U256,
+}
+
+/// Number of blocks a phase-change transition must be buried under before
+/// it is trusted as authoritative, absent any `UserData` override. Chosen
+/// to swallow the short reorgs typically seen in practice without unduly
+/// delaying `confirmed_phase`.
+const DEFAULT_CONFIRMATION_DEPTH: u64 = 6;
+
+fn confirmation_depth<M: Middleware + 'static>(
+    env: &StateFoldEnvironment<M, EpochFoldUserData>,
+) -> U256 {
+    env.user_data()
+        .confirmation_depth
+        .unwrap_or(U256::from(DEFAULT_CONFIRMATION_DEPTH))
+}
+
+/// Drains `pending`, promoting into `confirmed` every transition that is
+/// now buried at least `depth` blocks under `tip`. Transitions are kept in
+/// arrival order, so the first one that isn't deep enough yet stops the
+/// walk. `confirmed` is `None` for as long as nothing has ever been buried
+/// deep enough to promote - it is not seeded from any not-yet-confirmed
+/// phase.
+fn promote_confirmed_transitions(
+    pending: &mut VecDeque<PendingTransition>,
+    confirmed: &Option<Arc<ContractPhase>>,
+    tip: U256,
+    depth: U256,
+) -> Option<Arc<ContractPhase>> {
+    let mut confirmed = confirmed.clone();
+    while let Some(transition) = pending.front() {
+        if tip.saturating_sub(transition.block_number) >= depth {
+            confirmed = Some(Arc::clone(&transition.phase));
+            pending.pop_front();
+        } else {
+            break;
+        }
+    }
+    confirmed
+}
+
+/// Walks `pending` re-checking each entry's recorded block hash against the
+/// canonical chain. The first mismatch means that block was reorged out, so
+/// it and every transition after it (necessarily built on top of it) are
+/// dropped.
+async fn drop_reorged_transitions<M: Middleware + 'static>(
+    pending: &mut VecDeque<PendingTransition>,
+    middleware: &Arc<M>,
+) -> Result<(), FoldableError> {
+    let mut valid_upto = pending.len();
+    for (i, transition) in pending.iter().enumerate() {
+        let canonical = middleware
+            .get_block(transition.block_number.as_u64())
+            .await
+            .map_err(|e| FoldableError::from(Error::from(e)))?
+            .context("Block not found while reconciling pending phase transitions")?;
+
+        if canonical.hash != Some(transition.block_hash) {
+            valid_upto = i;
+            break;
+        }
+    }
+    pending.truncate(valid_upto);
+    Ok(())
+}
+
+/// Default capacity of each [`SubStateCache`] map, absent a `UserData`
+/// override.
+pub const DEFAULT_SUB_STATE_CACHE_CAPACITY: usize = 500;
+
+/// Hit/miss counters for a single cached sub-delegate lookup, exposed for
+/// observability.
+#[derive(Default, Debug)]
+pub struct CacheCounters {
+    pub hits: AtomicU64,
+    pub misses: AtomicU64,
+}
+
+impl CacheCounters {
+    fn record(&self, hit: bool) {
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Shared memoization cache for the `get_state_for_block` calls this
+/// delegate makes on its three sub-delegates. `env`/`UserData` is reused
+/// across every `sync`/`fold` invocation for a given state-fold instance,
+/// so a cache stashed here is effectively shared across adjacent blocks.
+///
+/// `accumulating`/`sealed` are keyed by block hash as well as call
+/// identity: their underlying aggregates can change every single block
+/// (new inputs, new claims), so a reorg (or the chain simply moving on)
+/// just means the old block-hash keys stop being queried and age out of
+/// the LRU on their own.
+///
+/// `finalized` is different: the only thing that ever grows it is a
+/// `PhaseChangeFilter { new_phase: 0 }` event (a dispute resolving back to
+/// `InputAccumulation`, which finalizes the epoch that was just disputed),
+/// so it is keyed by call identity alone and holds just the latest known
+/// value per `(address, initial_epoch)`. `cached_finalized_epochs` below
+/// uses the same bloom-filter check `fold` already does for phase changes
+/// to tell whether that latest value is still current before trusting it,
+/// so adjacent blocks that didn't touch the phase get a real cache hit
+/// instead of paying for another `get_state_for_block` round trip.
+pub struct SubStateCache {
+    finalized: Mutex<LruCache<(Address, U256), FinalizedEpochs>>,
+    pub finalized_counters: CacheCounters,
+    accumulating: Mutex<LruCache<(Address, U256, H256), AccumulatingEpoch>>,
+    pub accumulating_counters: CacheCounters,
+    sealed: Mutex<LruCache<(Address, U256, H256), SealedEpochState>>,
+    pub sealed_counters: CacheCounters,
+}
+
+impl SubStateCache {
+    pub fn new(capacity: usize) -> Self {
+        let capacity =
+            NonZeroUsize::new(capacity).unwrap_or_else(|| {
+                NonZeroUsize::new(DEFAULT_SUB_STATE_CACHE_CAPACITY).unwrap()
+            });
+
+        Self {
+            finalized: Mutex::new(LruCache::new(capacity)),
+            finalized_counters: CacheCounters::default(),
+            accumulating: Mutex::new(LruCache::new(capacity)),
+            accumulating_counters: CacheCounters::default(),
+            sealed: Mutex::new(LruCache::new(capacity)),
+            sealed_counters: CacheCounters::default(),
+        }
+    }
+}
+
+impl Default for SubStateCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SUB_STATE_CACHE_CAPACITY)
+    }
+}
+
+/// `UserData` threaded through every `sync`/`fold` call: the rolling
+/// finality confirmation depth and the shared sub-state cache.
+#[derive(Clone, Default)]
+pub struct EpochFoldUserData {
+    /// Optional override of the confirmation depth `K` used by the rolling
+    /// finality checker; defaults to `DEFAULT_CONFIRMATION_DEPTH` when unset.
+    pub confirmation_depth: Option<U256>,
+    /// Shared memoization cache for sub-delegate `get_state_for_block`
+    /// lookups.
+    pub sub_state_cache: Arc<SubStateCache>,
+}
+
+async fn cached_finalized_epochs<M: Middleware + 'static>(
+    cache: &SubStateCache,
+    dapp_contract_address: Address,
+    initial_epoch: U256,
+    block: &Block,
+    env: &StateFoldEnvironment<M, EpochFoldUserData>,
+) -> Result<FinalizedEpochs, FoldableError> {
+    let key = (dapp_contract_address, initial_epoch);
+
+    // The number of finalized epochs only ever changes via a
+    // PhaseChangeFilter event, so as long as this block's bloom rules that
+    // out, whatever was finalized as of the last block we checked is still
+    // exactly what's finalized now - a real hit across adjacent blocks,
+    // not just repeat calls for the same block hash.
+    let phase_may_have_changed = fold_utils::contains_address(
+        &block.logs_bloom,
+        &dapp_contract_address,
+    ) && fold_utils::contains_topic(
+        &block.logs_bloom,
+        &PhaseChangeFilter::signature(),
+    );
+
+    if !phase_may_have_changed {
+        if let Some(state) = cache.finalized.lock().unwrap().get(&key) {
+            cache.finalized_counters.record(true);
+            return Ok(state.clone());
+        }
+    }
+    cache.finalized_counters.record(false);
+
+    let state = FinalizedEpochs::get_state_for_block(
+        &(dapp_contract_address, initial_epoch),
+        block,
+        env,
+    )
+    .await
+    .context("Finalized epoch state fold error")?
+    .state;
+
+    cache.finalized.lock().unwrap().put(key, state.clone());
+    Ok(state)
+}
+
+async fn cached_accumulating_epoch<M: Middleware + 'static>(
+    cache: &SubStateCache,
+    dapp_contract_address: Address,
+    epoch_number: U256,
+    block: &Block,
+    env: &StateFoldEnvironment<M, EpochFoldUserData>,
+) -> Result<AccumulatingEpoch, FoldableError> {
+    let key = (dapp_contract_address, epoch_number, block.hash);
+    if let Some(state) = cache.accumulating.lock().unwrap().get(&key) {
+        cache.accumulating_counters.record(true);
+        return Ok(state.clone());
+    }
+    cache.accumulating_counters.record(false);
+
+    let state = AccumulatingEpoch::get_state_for_block(
+        &(dapp_contract_address, epoch_number),
+        block,
+        env,
+    )
+    .await?
+    .state;
+
+    cache.accumulating.lock().unwrap().put(key, state.clone());
+    Ok(state)
+}
+
+async fn cached_sealed_epoch<M: Middleware + 'static>(
+    cache: &SubStateCache,
+    dapp_contract_address: Address,
+    epoch_number: U256,
+    block: &Block,
+    env: &StateFoldEnvironment<M, EpochFoldUserData>,
+) -> Result<SealedEpochState, FoldableError> {
+    let key = (dapp_contract_address, epoch_number, block.hash);
+    if let Some(state) = cache.sealed.lock().unwrap().get(&key) {
+        cache.sealed_counters.record(true);
+        return Ok(state.clone());
+    }
+    cache.sealed_counters.record(false);
+
+    let state = SealedEpochState::get_state_for_block(
+        &(dapp_contract_address, epoch_number),
+        block,
+        env,
+    )
+    .await?
+    .state;
+
+    cache.sealed.lock().unwrap().put(key, state.clone());
+    Ok(state)
+}
+
 #[derive(Clone, Debug)]
 pub struct EpochState {
     pub initial_epoch: U256,
-    pub current_phase: ContractPhase,
+    /// `Arc`-wrapped so that the common "nothing changed this block" fold
+    /// path can carry it forward with a refcount bump instead of deep
+    /// cloning whatever claims data the phase is carrying.
+    pub current_phase: Arc<ContractPhase>,
+    /// Rolling-finality-filtered phase: only set once some transition has
+    /// been buried under the confirmation depth, so a short reorg cannot
+    /// make it flap back and forth. `None` until that has happened at
+    /// least once - it is never seeded from a phase that hasn't actually
+    /// been confirmed yet. Prefer this over `current_phase` for any
+    /// decision (e.g. submitting or challenging a claim) that shouldn't
+    /// act on a phase that could still be rolled back.
+    pub confirmed_phase: Option<Arc<ContractPhase>>,
+    /// Phase-change transitions observed at the tip that have not yet been
+    /// buried deep enough to be promoted into `confirmed_phase`.
+    pending_transitions: VecDeque<PendingTransition>,
     pub finalized_epochs: FinalizedEpochs,
     pub current_epoch: AccumulatingEpoch,
     /// Timestamp of last contract phase change
@@ -40,6 +321,422 @@ pub struct EpochState {
     dapp_contract_address: Address,
 }
 
+/// Bumped whenever `EpochStateSnapshot`'s layout changes, so an older
+/// snapshot can be rejected up front instead of misread.
+pub const EPOCH_STATE_SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Serializable twin of [`PendingTransition`] for use inside
+/// [`EpochStateSnapshot`]: `phase` is stored unwrapped since there is no
+/// reorg-hot fold loop to amortize an `Arc` clone over here.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingTransitionSnapshot {
+    pub phase: ContractPhase,
+    pub block_number: U256,
+    pub block_hash: H256,
+    pub timestamp: U256,
+}
+
+/// A warp-style point-in-time snapshot of an [`EpochState`], cheap to
+/// persist and restore so a cold start can fold forward from
+/// `block_number` instead of replaying the full phase-change history from
+/// genesis.
+///
+/// Includes `confirmed_phase`/`pending_transitions` as they stood at
+/// capture time: collapsing them into "already confirmed" on restore would
+/// silently discard whatever reorg exposure a not-yet-buried transition
+/// still had when the snapshot was taken.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EpochStateSnapshot {
+    pub format_version: u32,
+    pub block_number: U256,
+    pub block_hash: H256,
+    pub initial_epoch: U256,
+    pub finalized_epochs: FinalizedEpochs,
+    pub current_phase: ContractPhase,
+    pub confirmed_phase: Option<ContractPhase>,
+    pub pending_transitions: Vec<PendingTransitionSnapshot>,
+    pub current_epoch: AccumulatingEpoch,
+    pub phase_change_timestamp: Option<U256>,
+    pub dapp_contract_address: Address,
+}
+
+impl EpochState {
+    /// Captures this state as of `block`, for later restoration via
+    /// [`EpochState::validate_snapshot`] and [`EpochState::from_snapshot`].
+    pub fn to_snapshot(&self, block: &Block) -> EpochStateSnapshot {
+        EpochStateSnapshot {
+            format_version: EPOCH_STATE_SNAPSHOT_FORMAT_VERSION,
+            block_number: block.number,
+            block_hash: block.hash,
+            initial_epoch: self.initial_epoch,
+            finalized_epochs: self.finalized_epochs.clone(),
+            current_phase: self.current_phase.as_ref().clone(),
+            confirmed_phase: self
+                .confirmed_phase
+                .as_ref()
+                .map(|phase| phase.as_ref().clone()),
+            pending_transitions: self
+                .pending_transitions
+                .iter()
+                .map(|transition| PendingTransitionSnapshot {
+                    phase: transition.phase.as_ref().clone(),
+                    block_number: transition.block_number,
+                    block_hash: transition.block_hash,
+                    timestamp: transition.timestamp,
+                })
+                .collect(),
+            current_epoch: self.current_epoch.clone(),
+            phase_change_timestamp: self.phase_change_timestamp,
+            dapp_contract_address: self.dapp_contract_address,
+        }
+    }
+
+    /// Checks `snapshot` is trustworthy before it is used to warm-start a
+    /// `sync`: the format must be one this build understands, and the
+    /// number of finalized epochs it claims must still match what the
+    /// contract reports as of `block` (which must be the block numbered
+    /// `snapshot.block_number`). Does not mutate or consume the snapshot,
+    /// so a failed validation can fall back to a full `sync` from genesis.
+    pub async fn validate_snapshot<M: Middleware + 'static>(
+        snapshot: &EpochStateSnapshot,
+        block: &Block,
+        env: &StateFoldEnvironment<M, EpochFoldUserData>,
+    ) -> Result<(), FoldableError> {
+        if snapshot.format_version != EPOCH_STATE_SNAPSHOT_FORMAT_VERSION {
+            return Err(anyhow!(
+                "Unsupported EpochStateSnapshot format version `{}`, expected `{}`",
+                snapshot.format_version,
+                EPOCH_STATE_SNAPSHOT_FORMAT_VERSION
+            )
+            .into());
+        }
+
+        if block.number != snapshot.block_number || block.hash != snapshot.block_hash {
+            return Err(anyhow!(
+                "Snapshot was taken at block {}/{:?}, but block {}/{:?} was given to validate it",
+                snapshot.block_number,
+                snapshot.block_hash,
+                block.number,
+                block.hash,
+            )
+            .into());
+        }
+
+        let on_chain_finalized_epochs = cached_finalized_epochs(
+            &env.user_data().sub_state_cache,
+            snapshot.dapp_contract_address,
+            snapshot.initial_epoch,
+            block,
+            env,
+        )
+        .await?;
+
+        if on_chain_finalized_epochs.next_epoch() != snapshot.finalized_epochs.next_epoch()
+        {
+            return Err(anyhow!(
+                "Snapshot claims {} finalized epochs, but the contract reports {} as of block {}",
+                snapshot.finalized_epochs.next_epoch(),
+                on_chain_finalized_epochs.next_epoch(),
+                block.number,
+            )
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Seeds an `EpochState` from a snapshot that has already passed
+    /// [`EpochState::validate_snapshot`]. The caller is expected to resume
+    /// folding forward from `snapshot.block_number` rather than calling
+    /// `sync`, since that history has already been accounted for.
+    ///
+    /// `confirmed_phase`/`pending_transitions` are carried through exactly
+    /// as captured, rather than collapsed into "already confirmed": doing
+    /// that would silently discard whatever reorg exposure still existed
+    /// at capture time. The restored phase is additionally queued as a new
+    /// pending transition tagged with the snapshot's own block, since that
+    /// block might itself still get reorged out and `fold`'s usual
+    /// reorg/promotion bookkeeping is what catches that going forward.
+    pub fn from_snapshot(snapshot: EpochStateSnapshot) -> Self {
+        let current_phase = Arc::new(snapshot.current_phase);
+        let confirmed_phase = snapshot.confirmed_phase.map(Arc::new);
+        let mut pending_transitions: VecDeque<PendingTransition> = snapshot
+            .pending_transitions
+            .into_iter()
+            .map(|transition| PendingTransition {
+                phase: Arc::new(transition.phase),
+                block_number: transition.block_number,
+                block_hash: transition.block_hash,
+                timestamp: transition.timestamp,
+            })
+            .collect();
+        pending_transitions.push_back(PendingTransition {
+            phase: Arc::clone(&current_phase),
+            block_number: snapshot.block_number,
+            block_hash: snapshot.block_hash,
+            timestamp: snapshot.phase_change_timestamp.unwrap_or_default(),
+        });
+
+        EpochState {
+            current_phase,
+            confirmed_phase,
+            pending_transitions,
+            initial_epoch: snapshot.initial_epoch,
+            finalized_epochs: snapshot.finalized_epochs,
+            current_epoch: snapshot.current_epoch,
+            phase_change_timestamp: snapshot.phase_change_timestamp,
+            dapp_contract_address: snapshot.dapp_contract_address,
+        }
+    }
+
+    /// Tallies the claims submitted for the sealed epoch behind
+    /// `AwaitingConsensus`/`AwaitingDispute`, so a dispatcher can decide
+    /// whether to submit the first claim, agree with the majority, or
+    /// challenge a conflicting one. `None` in `InputAccumulation`, where
+    /// there is no sealed epoch to have claims at all, and also `None`
+    /// while nothing has been confirmed yet - reading `current_phase`
+    /// here would hand a dispatcher a decision based on a phase that could
+    /// still be rolled back by a reorg moments later.
+    pub fn consensus_status(&self) -> Option<ConsensusStatus> {
+        let claims = match self.confirmed_phase.as_deref()? {
+            ContractPhase::InputAccumulation {} => return None,
+            ContractPhase::AwaitingConsensus { sealed_epoch, .. } => {
+                match sealed_epoch.claims() {
+                    Some(claims) => claims,
+                    // Sealed but unclaimed: an empty tally, not `None`.
+                    None => return Some(ConsensusStatus::tally(&HashMap::new())),
+                }
+            }
+            ContractPhase::AwaitingDispute { sealed_epoch } => {
+                sealed_epoch.claims()
+            }
+        };
+
+        Some(ConsensusStatus::tally(claims))
+    }
+}
+
+/// Outcome of tallying the claims submitted for a sealed epoch: which hash
+/// (if any) has a majority, and which claimants disagree with it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsensusStatus {
+    /// The most-claimed hash so far, if any claim has been submitted.
+    pub winning_claim: Option<H256>,
+    /// Whether `winning_claim` is backed by a strict majority of the
+    /// claimants seen so far.
+    pub has_majority: bool,
+    /// Every claimant whose submitted hash differs from `winning_claim`, in
+    /// `AwaitingDispute` as in `AwaitingConsensus`. Which pair of claims
+    /// triggered a dispute is not tracked.
+    pub dissenting_claimants: Vec<Address>,
+}
+
+impl ConsensusStatus {
+    fn tally(claims: &HashMap<Address, H256>) -> Self {
+        let mut counts: HashMap<H256, u64> = HashMap::new();
+        for hash in claims.values() {
+            *counts.entry(*hash).or_insert(0) += 1;
+        }
+
+        // `HashMap` iteration order is randomized per-process, so breaking
+        // ties by whichever entry `max_by_key` visits last would make the
+        // winning claim non-deterministic across nodes (or even across
+        // restarts of the same node) whenever two hashes are tied on
+        // count. Break ties on the hash itself so every observer of the
+        // same claims agrees on the same winner.
+        let winning_claim = counts
+            .iter()
+            .max_by_key(|(hash, count)| (**count, **hash))
+            .map(|(hash, _)| *hash);
+
+        let has_majority = winning_claim
+            .map(|hash| counts[&hash] * 2 > claims.len() as u64)
+            .unwrap_or(false);
+
+        let dissenting_claimants = claims
+            .iter()
+            .filter(|(_, hash)| Some(**hash) != winning_claim)
+            .map(|(claimant, _)| *claimant)
+            .collect();
+
+        ConsensusStatus {
+            winning_claim,
+            has_majority,
+            dissenting_claimants,
+        }
+    }
+}
+
+impl EpochState {
+    /// Reconstructs the `ContractPhase` that actually governed `epoch_number`,
+    /// analogous to PoA's `epoch_transition_for(parent)`: rather than only
+    /// ever reassembling state for whatever epoch is active at `block`, this
+    /// walks the phase-change history backwards from `block` to find the
+    /// specific event that governed `epoch_number` and assembles the
+    /// matching `SealedEpochState`/`AccumulatingEpoch` as of *that* event's
+    /// own block. Lets a caller audit a past consensus/dispute round
+    /// directly by epoch number instead of only ever seeing the current
+    /// phase, and without having to guess which block to pass in.
+    ///
+    /// `block` only bounds how far forward the search looks; it does not
+    /// need to be the block at which `epoch_number` was active.
+    pub async fn state_for_epoch<M: Middleware + 'static>(
+        dapp_contract_address: Address,
+        epoch_number: U256,
+        block: &Block,
+        env: &StateFoldEnvironment<M, EpochFoldUserData>,
+    ) -> Result<ContractPhase, FoldableError> {
+        let middleware = env.inner_middleware();
+        let contract =
+            RollupsFacet::new(dapp_contract_address, Arc::clone(&middleware));
+
+        // Single bounded query over the whole history, walked locally
+        // below, instead of reissuing it on every caller retry.
+        let phase_change_events = contract
+            .phase_change_filter()
+            .to_block(block.number)
+            .query_with_meta()
+            .await
+            .context("Error querying for rollups phase change")?;
+
+        for (event, meta) in phase_change_events.iter().rev() {
+            let event_block: Block = middleware
+                .get_block(meta.block_hash)
+                .await
+                .map_err(|e| FoldableError::from(Error::from(e)))?
+                .context("Block not found while searching phase-change history")?
+                .into();
+
+            // `initial_epoch` only affects how far back `FinalizedEpochs`
+            // needs to replay from, not the aggregate it converges to, so
+            // zero is safe here the same way it is in the fallback below.
+            let finalized_epochs = cached_finalized_epochs(
+                &env.user_data().sub_state_cache,
+                dapp_contract_address,
+                U256::zero(),
+                &event_block,
+                env,
+            )
+            .await?;
+            let next_epoch = finalized_epochs.next_epoch();
+
+            if next_epoch > epoch_number {
+                // `epoch_number` was already finalized by this event; keep
+                // walking backwards to find the event that governed it
+                // before that happened.
+                continue;
+            }
+            if next_epoch < epoch_number {
+                // Walked past the point where `epoch_number` had even
+                // started; no earlier event can govern it either.
+                break;
+            }
+
+            return Ok(match event.new_phase {
+                0 => ContractPhase::InputAccumulation {},
+
+                1 => {
+                    let sealed_epoch = cached_sealed_epoch(
+                        &env.user_data().sub_state_cache,
+                        dapp_contract_address,
+                        epoch_number,
+                        &event_block,
+                        env,
+                    )
+                    .await?;
+
+                    ContractPhase::AwaitingConsensus {
+                        sealed_epoch,
+                        round_start: event_block.timestamp,
+                    }
+                }
+
+                2 => {
+                    let sealed_epoch = cached_sealed_epoch(
+                        &env.user_data().sub_state_cache,
+                        dapp_contract_address,
+                        epoch_number,
+                        &event_block,
+                        env,
+                    )
+                    .await?;
+
+                    ContractPhase::AwaitingDispute {
+                        sealed_epoch: match sealed_epoch {
+                            SealedEpochState::SealedEpochNoClaims {
+                                sealed_epoch,
+                            } => {
+                                return Err(anyhow!(
+                                    "Illegal state for AwaitingDispute: {:?}",
+                                    sealed_epoch
+                                )
+                                .into());
+                            }
+                            SealedEpochState::SealedEpochWithClaims {
+                                claimed_epoch,
+                            } => claimed_epoch,
+                        },
+                    }
+                }
+
+                new_phase => {
+                    return Err(anyhow!(
+                        "Could not convert new_phase `{}` to PhaseState",
+                        new_phase
+                    )
+                    .into());
+                }
+            });
+        }
+
+        // No phase-change event ever governed `epoch_number`: it's either
+        // the default InputAccumulation phase an epoch starts in before any
+        // event touches it, already finalized before the earliest event we
+        // looked at, or hasn't started yet.
+        let finalized_epochs = cached_finalized_epochs(
+            &env.user_data().sub_state_cache,
+            dapp_contract_address,
+            U256::zero(),
+            block,
+            env,
+        )
+        .await?;
+        let next_epoch = finalized_epochs.next_epoch();
+
+        // While the tip is AwaitingConsensus/AwaitingDispute, `next_epoch`
+        // is the sealed epoch - but a second epoch, `next_epoch + 1`, is
+        // concurrently accumulating inputs right now and has never had a
+        // phase-change event of its own, so the loop above can't find it
+        // either. It's always InputAccumulation until it gets sealed.
+        let tip_is_awaiting_consensus_or_dispute = matches!(
+            phase_change_events.last(),
+            Some((PhaseChangeFilter { new_phase: 1 | 2 }, _))
+        );
+
+        if epoch_number == next_epoch
+            || (tip_is_awaiting_consensus_or_dispute
+                && epoch_number == next_epoch + 1u64)
+        {
+            Ok(ContractPhase::InputAccumulation {})
+        } else if epoch_number < next_epoch {
+            Err(anyhow!(
+                "Epoch {} was already finalized by block {}",
+                epoch_number,
+                block.number
+            )
+            .into())
+        } else {
+            Err(anyhow!(
+                "Epoch {} had not started yet as of block {} (next epoch was {})",
+                epoch_number,
+                block.number,
+                next_epoch
+            )
+            .into())
+        }
+    }
+}
+
 /// Epoch StateActor Delegate, which implements `sync` and `fold`.
 /// It uses the subdelegates to extracts the raw state from blockchain
 /// emitted events
@@ -47,7 +744,7 @@ pub struct EpochState {
 impl Foldable for EpochState {
     type InitialState = (Address, U256);
     type Error = FoldableError;
-    type UserData = ();
+    type UserData = EpochFoldUserData;
 
     async fn sync<M: Middleware + 'static>(
         initial_state: &Self::InitialState,
@@ -62,14 +759,14 @@ impl Foldable for EpochState {
             RollupsFacet::new(dapp_contract_address, Arc::clone(&middleware));
 
         // retrieve list of finalized epochs from FinalizedEpochFoldDelegate
-        let finalized_epochs = FinalizedEpochs::get_state_for_block(
-            &(dapp_contract_address, initial_epoch),
+        let finalized_epochs = cached_finalized_epochs(
+            &env.user_data().sub_state_cache,
+            dapp_contract_address,
+            initial_epoch,
             block,
             env,
         )
-        .await
-        .context("Finalized epoch state fold error")?
-        .state;
+        .await?;
 
         // The index of next epoch is the number of finalized epochs
         let next_epoch = finalized_epochs.next_epoch();
@@ -101,34 +798,37 @@ impl Foldable for EpochState {
             // InputAccumulation
             // either accumulating inputs or sealed epoch with no claims/new inputs
             Some((PhaseChangeFilter { new_phase: 0 }, _)) | None => {
-                let current_epoch = AccumulatingEpoch::get_state_for_block(
-                    &(dapp_contract_address, next_epoch),
+                let current_epoch = cached_accumulating_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
                 (ContractPhase::InputAccumulation {}, current_epoch)
             }
 
             // AwaitingConsensus
             // can be SealedEpochNoClaims or SealedEpochWithClaims
             Some((PhaseChangeFilter { new_phase: 1 }, _)) => {
-                let sealed_epoch = SealedEpochState::get_state_for_block(
-                    &(dapp_contract_address, next_epoch),
+                let sealed_epoch = cached_sealed_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
 
-                let current_epoch = AccumulatingEpoch::get_state_for_block(
-                    &(dapp_contract_address, next_epoch + 1u64),
+                let current_epoch = cached_accumulating_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch + 1u64,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
 
                 // Unwrap is safe because, a phase change event guarantees
                 // a phase change timestamp
@@ -145,21 +845,23 @@ impl Foldable for EpochState {
 
             // AwaitingDispute
             Some((PhaseChangeFilter { new_phase: 2 }, _)) => {
-                let sealed_epoch = SealedEpochState::get_state_for_block(
-                    &(dapp_contract_address, next_epoch),
+                let sealed_epoch = cached_sealed_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
 
-                let current_epoch = AccumulatingEpoch::get_state_for_block(
-                    &(dapp_contract_address, next_epoch + 1u64),
+                let current_epoch = cached_accumulating_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch + 1u64,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
 
                 (
                     ContractPhase::AwaitingDispute {
@@ -194,8 +896,32 @@ impl Foldable for EpochState {
             }
         };
 
+        // A freshly-synced delegate has no history to replay a reorg
+        // against, but the tip block it just synced at can still be
+        // reorged out a moment later. Queue the observed phase as a
+        // pending transition tagged with that block instead of trusting it
+        // outright, so the usual promotion/reorg bookkeeping in `fold`
+        // applies to it exactly like any other transition - `confirmed_phase`
+        // starts at `None` and only becomes `Some` once it clears `depth`.
+        let current_phase = Arc::new(current_phase);
+        let mut pending_transitions = VecDeque::new();
+        pending_transitions.push_back(PendingTransition {
+            phase: Arc::clone(&current_phase),
+            block_number: block.number,
+            block_hash: block.hash,
+            timestamp: block.timestamp,
+        });
+        let confirmed_phase = promote_confirmed_transitions(
+            &mut pending_transitions,
+            &None,
+            block.number,
+            confirmation_depth(env),
+        );
+
         Ok(EpochState {
             current_phase,
+            confirmed_phase,
+            pending_transitions,
             phase_change_timestamp,
             initial_epoch,
             finalized_epochs,
@@ -208,9 +934,11 @@ impl Foldable for EpochState {
         previous_state: &Self,
         block: &Block,
         env: &StateFoldEnvironment<M, Self::UserData>,
-        _access: Arc<FoldMiddleware<M>>,
+        access: Arc<FoldMiddleware<M>>,
     ) -> Result<Self, Self::Error> {
         let dapp_contract_address = previous_state.dapp_contract_address;
+        let depth = confirmation_depth(env);
+
         // Check if there was (possibly) some log emited on this block.
         if !(fold_utils::contains_address(
             &block.logs_bloom,
@@ -221,18 +949,16 @@ impl Foldable for EpochState {
         )) {
             // Current phase has not changed, but we need to update the
             // sub-states.
-            let current_epoch = AccumulatingEpoch::get_state_for_block(
-                &(
-                    dapp_contract_address,
-                    previous_state.current_epoch.epoch_number,
-                ),
+            let current_epoch = cached_accumulating_epoch(
+                &env.user_data().sub_state_cache,
+                dapp_contract_address,
+                previous_state.current_epoch.epoch_number,
                 block,
                 env,
             )
-            .await?
-            .state;
+            .await?;
 
-            let current_phase = match &previous_state.current_phase {
+            let current_phase = match previous_state.current_phase.as_ref() {
                 ContractPhase::InputAccumulation {} => {
                     ContractPhase::InputAccumulation {}
                 }
@@ -241,13 +967,14 @@ impl Foldable for EpochState {
                     sealed_epoch,
                     round_start,
                 } => {
-                    let sealed_epoch = SealedEpochState::get_state_for_block(
-                        &(dapp_contract_address, sealed_epoch.epoch_number()),
+                    let sealed_epoch = cached_sealed_epoch(
+                        &env.user_data().sub_state_cache,
+                        dapp_contract_address,
+                        sealed_epoch.epoch_number(),
                         block,
                         env,
                     )
-                    .await?
-                    .state;
+                    .await?;
 
                     ContractPhase::AwaitingConsensus {
                         sealed_epoch,
@@ -256,13 +983,14 @@ impl Foldable for EpochState {
                 }
 
                 ContractPhase::AwaitingDispute { sealed_epoch } => {
-                    let sealed_epoch = SealedEpochState::get_state_for_block(
-                        &(dapp_contract_address, sealed_epoch.epoch_number),
+                    let sealed_epoch = cached_sealed_epoch(
+                        &env.user_data().sub_state_cache,
+                        dapp_contract_address,
+                        sealed_epoch.epoch_number,
                         block,
                         env,
                     )
-                    .await?
-                    .state;
+                    .await?;
 
                     ContractPhase::AwaitingDispute {
                         sealed_epoch: match sealed_epoch {
@@ -282,9 +1010,29 @@ impl Foldable for EpochState {
                     }
                 }
             };
+            let current_phase = Arc::new(current_phase);
+
+            // No new phase change this block: just walk the pending queue,
+            // dropping anything reorged out and promoting anything now
+            // buried deep enough.
+            let mut pending_transitions =
+                previous_state.pending_transitions.clone();
+            drop_reorged_transitions(
+                &mut pending_transitions,
+                &access.get_inner(),
+            )
+            .await?;
+            let confirmed_phase = promote_confirmed_transitions(
+                &mut pending_transitions,
+                &previous_state.confirmed_phase,
+                block.number,
+                depth,
+            );
 
             return Ok(EpochState {
                 current_phase,
+                confirmed_phase,
+                pending_transitions,
                 current_epoch,
                 phase_change_timestamp: previous_state.phase_change_timestamp,
                 initial_epoch: previous_state.initial_epoch,
@@ -296,13 +1044,14 @@ impl Foldable for EpochState {
         let middleware = env.inner_middleware();
         let contract = RollupsFacet::new(dapp_contract_address, middleware);
 
-        let finalized_epochs = FinalizedEpochs::get_state_for_block(
-            &(dapp_contract_address, previous_state.initial_epoch),
+        let finalized_epochs = cached_finalized_epochs(
+            &env.user_data().sub_state_cache,
+            dapp_contract_address,
+            previous_state.initial_epoch,
             block,
             env,
         )
-        .await?
-        .state;
+        .await?;
 
         let next_epoch = finalized_epochs.next_epoch();
 
@@ -315,13 +1064,14 @@ impl Foldable for EpochState {
         let (current_phase, current_epoch) = match phase_change_events.last() {
             // InputAccumulation
             Some(PhaseChangeFilter { new_phase: 0 }) | None => {
-                let current_epoch = AccumulatingEpoch::get_state_for_block(
-                    &(dapp_contract_address, next_epoch),
+                let current_epoch = cached_accumulating_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
                 (ContractPhase::InputAccumulation {}, current_epoch)
             }
 
@@ -330,20 +1080,22 @@ impl Foldable for EpochState {
                 // If the phase is AwaitingConsensus then there are two epochs
                 // not yet finalized. One sealead, which can't receive new
                 // inputs and one active, accumulating new inputs
-                let sealed_epoch = SealedEpochState::get_state_for_block(
-                    &(dapp_contract_address, next_epoch),
+                let sealed_epoch = cached_sealed_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch,
                     block,
                     env,
                 )
-                .await?
-                .state;
-                let current_epoch = AccumulatingEpoch::get_state_for_block(
-                    &(dapp_contract_address, next_epoch + 1u64),
+                .await?;
+                let current_epoch = cached_accumulating_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch + 1u64,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
 
                 // Timestamp of when we entered this phase.
                 let round_start = block.timestamp;
@@ -362,21 +1114,23 @@ impl Foldable for EpochState {
                 // If the phase is AwaitingDispute then there are two epochs
                 // not yet finalized. One sealead, which can't receive new
                 // inputs and one active, accumulating new inputs
-                let sealed_epoch = SealedEpochState::get_state_for_block(
-                    &(dapp_contract_address, next_epoch),
+                let sealed_epoch = cached_sealed_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
 
-                let current_epoch = AccumulatingEpoch::get_state_for_block(
-                    &(dapp_contract_address, next_epoch + 1u64),
+                let current_epoch = cached_accumulating_epoch(
+                    &env.user_data().sub_state_cache,
+                    dapp_contract_address,
+                    next_epoch + 1u64,
                     block,
                     env,
                 )
-                .await?
-                .state;
+                .await?;
 
                 (
                     ContractPhase::AwaitingDispute {
@@ -414,9 +1168,33 @@ impl Foldable for EpochState {
         } else {
             Some(block.timestamp)
         };
+        let current_phase = Arc::new(current_phase);
+
+        // A phase change happened at this block: queue it as a pending
+        // transition rather than trusting it outright, then replay the
+        // same promotion/reorg bookkeeping as the no-change branch above.
+        let mut pending_transitions = previous_state.pending_transitions.clone();
+        drop_reorged_transitions(&mut pending_transitions, &access.get_inner())
+            .await?;
+        if !phase_change_events.is_empty() {
+            pending_transitions.push_back(PendingTransition {
+                phase: Arc::clone(&current_phase),
+                block_number: block.number,
+                block_hash: block.hash,
+                timestamp: block.timestamp,
+            });
+        }
+        let confirmed_phase = promote_confirmed_transitions(
+            &mut pending_transitions,
+            &previous_state.confirmed_phase,
+            block.number,
+            depth,
+        );
 
         Ok(EpochState {
             current_phase,
+            confirmed_phase,
+            pending_transitions,
             current_epoch,
             phase_change_timestamp,
             initial_epoch: previous_state.initial_epoch,
@@ -424,4 +1202,292 @@ impl Foldable for EpochState {
             dapp_contract_address,
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sealed_epoch::SealedEpochNoClaims;
+    use ethers::providers::{MockProvider, Provider};
+    use ethers::types::Block as EthBlock;
+
+    fn transition(
+        phase: &Arc<ContractPhase>,
+        block_number: u64,
+        block_hash: H256,
+    ) -> PendingTransition {
+        PendingTransition {
+            phase: Arc::clone(phase),
+            block_number: U256::from(block_number),
+            block_hash,
+            timestamp: U256::zero(),
+        }
+    }
+
+    #[test]
+    fn promote_confirmed_transitions_stops_at_first_unburied() {
+        let genesis = Arc::new(ContractPhase::InputAccumulation {});
+        let consensus = Arc::new(ContractPhase::AwaitingConsensus {
+            sealed_epoch: SealedEpochState::SealedEpochNoClaims {
+                sealed_epoch: SealedEpochNoClaims {
+                    epoch_number: U256::zero(),
+                    dapp_contract_address: Address::zero(),
+                },
+            },
+            round_start: U256::zero(),
+        });
+        let dispute = Arc::new(ContractPhase::AwaitingDispute {
+            sealed_epoch: EpochWithClaims::new(
+                U256::zero(),
+                Address::zero(),
+                HashMap::new(),
+            ),
+        });
+
+        let mut pending = VecDeque::new();
+        pending.push_back(transition(&consensus, 10, H256::repeat_byte(1)));
+        pending.push_back(transition(&dispute, 16, H256::repeat_byte(2)));
+
+        // Tip is 6 blocks past the first transition (exactly depth 6), so it
+        // is promoted, but the second one (buried 0 blocks) must not be yet.
+        let confirmed = promote_confirmed_transitions(
+            &mut pending,
+            &Some(Arc::clone(&genesis)),
+            U256::from(16u64),
+            U256::from(6u64),
+        );
+
+        assert!(matches!(
+            confirmed.as_deref(),
+            Some(ContractPhase::AwaitingConsensus { .. })
+        ));
+        assert_eq!(pending.len(), 1);
+        assert!(matches!(
+            pending.front().unwrap().phase.as_ref(),
+            ContractPhase::AwaitingDispute { .. }
+        ));
+    }
+
+    #[test]
+    fn promote_confirmed_transitions_promotes_everything_buried_deep_enough() {
+        let genesis = Arc::new(ContractPhase::InputAccumulation {});
+        let consensus = Arc::new(ContractPhase::AwaitingConsensus {
+            sealed_epoch: SealedEpochState::SealedEpochNoClaims {
+                sealed_epoch: SealedEpochNoClaims {
+                    epoch_number: U256::zero(),
+                    dapp_contract_address: Address::zero(),
+                },
+            },
+            round_start: U256::zero(),
+        });
+
+        let mut pending = VecDeque::new();
+        pending.push_back(transition(&consensus, 10, H256::repeat_byte(1)));
+
+        let confirmed = promote_confirmed_transitions(
+            &mut pending,
+            &Some(Arc::clone(&genesis)),
+            U256::from(16u64),
+            U256::from(6u64),
+        );
+
+        assert!(pending.is_empty());
+        assert!(matches!(
+            confirmed.as_deref(),
+            Some(ContractPhase::AwaitingConsensus { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn drop_reorged_transitions_truncates_at_first_mismatch() {
+        let genesis = Arc::new(ContractPhase::InputAccumulation {});
+
+        let good_hash = H256::repeat_byte(1);
+        let stale_hash = H256::repeat_byte(2);
+        let canonical_hash = H256::repeat_byte(9);
+
+        let mut pending = VecDeque::new();
+        pending.push_back(transition(&genesis, 10, good_hash));
+        pending.push_back(transition(&genesis, 11, stale_hash));
+
+        let mock = MockProvider::new();
+        let mut block_at_10 = EthBlock::<H256>::default();
+        block_at_10.hash = Some(good_hash);
+        // The reorg replaced block 11 with a different canonical hash than
+        // what was recorded when the transition was queued.
+        let mut block_at_11 = EthBlock::<H256>::default();
+        block_at_11.hash = Some(canonical_hash);
+
+        // `drop_reorged_transitions` walks oldest-to-newest, and the mock
+        // serves the most recently pushed response first, so responses are
+        // pushed newest-first.
+        mock.push(block_at_11).unwrap();
+        mock.push(block_at_10).unwrap();
+
+        let middleware = Arc::new(Provider::new(mock));
+
+        drop_reorged_transitions(&mut pending, &middleware)
+            .await
+            .unwrap();
+
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending.front().unwrap().block_hash, good_hash);
+    }
+
+    #[test]
+    fn tally_picks_the_majority_claim() {
+        let majority_hash = H256::repeat_byte(1);
+        let minority_hash = H256::repeat_byte(2);
+        let claimant_a = Address::repeat_byte(0xa);
+        let claimant_b = Address::repeat_byte(0xb);
+        let claimant_c = Address::repeat_byte(0xc);
+
+        let mut claims = HashMap::new();
+        claims.insert(claimant_a, majority_hash);
+        claims.insert(claimant_b, majority_hash);
+        claims.insert(claimant_c, minority_hash);
+
+        let status = ConsensusStatus::tally(&claims);
+
+        assert_eq!(status.winning_claim, Some(majority_hash));
+        assert!(status.has_majority);
+        assert_eq!(status.dissenting_claimants, vec![claimant_c]);
+    }
+
+    #[test]
+    fn tally_reports_no_majority_on_an_even_split() {
+        let hash_a = H256::repeat_byte(1);
+        let hash_b = H256::repeat_byte(2);
+        let claimant_a = Address::repeat_byte(0xa);
+        let claimant_b = Address::repeat_byte(0xb);
+
+        let mut claims = HashMap::new();
+        claims.insert(claimant_a, hash_a);
+        claims.insert(claimant_b, hash_b);
+
+        let status = ConsensusStatus::tally(&claims);
+
+        assert!(!status.has_majority);
+        assert_eq!(status.dissenting_claimants.len(), 1);
+    }
+
+    #[test]
+    fn tally_of_no_claims_has_no_winner() {
+        let status = ConsensusStatus::tally(&HashMap::new());
+
+        assert_eq!(status.winning_claim, None);
+        assert!(!status.has_majority);
+        assert!(status.dissenting_claimants.is_empty());
+    }
+
+    #[test]
+    fn snapshot_round_trip_queues_restored_phase_as_pending_without_confirming_it() {
+        let phase = Arc::new(ContractPhase::InputAccumulation {});
+        let state = EpochState {
+            initial_epoch: U256::zero(),
+            current_phase: Arc::clone(&phase),
+            // Nothing has ever been confirmed yet - the round trip must not
+            // manufacture a confirmation out of the merely-observed phase.
+            confirmed_phase: None,
+            pending_transitions: VecDeque::new(),
+            finalized_epochs: FinalizedEpochs::default(),
+            current_epoch: AccumulatingEpoch::default(),
+            phase_change_timestamp: None,
+            dapp_contract_address: Address::repeat_byte(0x42),
+        };
+
+        let block = Block {
+            number: U256::from(100u64),
+            hash: H256::repeat_byte(7),
+            timestamp: U256::from(1_000u64),
+            logs_bloom: Default::default(),
+        };
+
+        let snapshot = state.to_snapshot(&block);
+        assert_eq!(snapshot.format_version, EPOCH_STATE_SNAPSHOT_FORMAT_VERSION);
+        assert_eq!(snapshot.block_number, block.number);
+        assert_eq!(snapshot.block_hash, block.hash);
+        assert!(snapshot.confirmed_phase.is_none());
+
+        let restored = EpochState::from_snapshot(snapshot);
+
+        assert!(matches!(
+            restored.current_phase.as_ref(),
+            ContractPhase::InputAccumulation {}
+        ));
+        assert!(restored.confirmed_phase.is_none());
+        // The restored phase must be queued as pending, not trusted
+        // outright, so a later `fold` can still roll it back if this block
+        // turns out to have been reorged.
+        assert_eq!(restored.pending_transitions.len(), 1);
+        assert_eq!(
+            restored.pending_transitions[0].block_number,
+            block.number
+        );
+        assert_eq!(restored.pending_transitions[0].block_hash, block.hash);
+    }
+
+    #[test]
+    fn snapshot_round_trip_preserves_prior_confirmed_phase_and_pending_queue() {
+        let confirmed = Arc::new(ContractPhase::InputAccumulation {});
+        let still_pending = Arc::new(ContractPhase::AwaitingConsensus {
+            sealed_epoch: SealedEpochState::SealedEpochNoClaims {
+                sealed_epoch: SealedEpochNoClaims {
+                    epoch_number: U256::zero(),
+                    dapp_contract_address: Address::zero(),
+                },
+            },
+            round_start: U256::zero(),
+        });
+
+        let mut pending_transitions = VecDeque::new();
+        pending_transitions.push_back(PendingTransition {
+            phase: Arc::clone(&still_pending),
+            block_number: U256::from(95u64),
+            block_hash: H256::repeat_byte(5),
+            timestamp: U256::zero(),
+        });
+
+        let state = EpochState {
+            initial_epoch: U256::zero(),
+            current_phase: Arc::clone(&still_pending),
+            confirmed_phase: Some(Arc::clone(&confirmed)),
+            pending_transitions,
+            finalized_epochs: FinalizedEpochs::default(),
+            current_epoch: AccumulatingEpoch::default(),
+            phase_change_timestamp: None,
+            dapp_contract_address: Address::repeat_byte(0x42),
+        };
+
+        let block = Block {
+            number: U256::from(100u64),
+            hash: H256::repeat_byte(7),
+            timestamp: U256::from(1_000u64),
+            logs_bloom: Default::default(),
+        };
+
+        let snapshot = state.to_snapshot(&block);
+        assert!(snapshot.confirmed_phase.is_some());
+        assert_eq!(snapshot.pending_transitions.len(), 1);
+
+        let restored = EpochState::from_snapshot(snapshot);
+
+        // The previously confirmed phase must survive the round trip
+        // unchanged, not get collapsed into whatever was merely observed.
+        assert!(matches!(
+            restored.confirmed_phase.as_deref(),
+            Some(ContractPhase::InputAccumulation {})
+        ));
+        // The pre-existing pending entry is carried through, and the
+        // newly-observed phase is queued alongside it rather than replacing it.
+        assert_eq!(restored.pending_transitions.len(), 2);
+        assert_eq!(
+            restored.pending_transitions[0].block_number,
+            U256::from(95u64)
+        );
+        assert_eq!(
+            restored.pending_transitions[1].block_number,
+            block.number
+        );
+    }
+}
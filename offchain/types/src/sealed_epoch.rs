@@ -0,0 +1,211 @@
+use crate::FoldableError;
+use anyhow::Context;
+use async_trait::async_trait;
+use contracts::rollups_facet::*;
+use ethers::{
+    prelude::EthEvent,
+    providers::Middleware,
+    types::{Address, H256, U256},
+};
+use serde::{Deserialize, Serialize};
+use state_fold::{
+    utils as fold_utils, FoldMiddleware, Foldable, StateFoldEnvironment,
+    SyncMiddleware,
+};
+use state_fold_types::{ethers, Block};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A sealed epoch that hasn't received any claims yet: still
+/// `AwaitingConsensus`, with nothing for `EpochState::consensus_status` to
+/// tally.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SealedEpochNoClaims {
+    pub epoch_number: U256,
+    pub dapp_contract_address: Address,
+}
+
+/// A sealed epoch with at least one submitted claim: the per-claimant
+/// `claimant -> claim hash` map that `EpochState::consensus_status` tallies
+/// into a `ConsensusStatus`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EpochWithClaims {
+    pub epoch_number: U256,
+    pub dapp_contract_address: Address,
+    claims: HashMap<Address, H256>,
+}
+
+impl EpochWithClaims {
+    pub(crate) fn new(
+        epoch_number: U256,
+        dapp_contract_address: Address,
+        claims: HashMap<Address, H256>,
+    ) -> Self {
+        Self {
+            epoch_number,
+            dapp_contract_address,
+            claims,
+        }
+    }
+
+    /// Per-claimant claim hashes submitted for this epoch so far.
+    pub fn claims(&self) -> &HashMap<Address, H256> {
+        &self.claims
+    }
+}
+
+/// Sub-delegate state for a single sealed epoch: whether any claims have
+/// been submitted for it yet and, if so, who claimed what. `AwaitingDispute`
+/// can only ever be reached from the `SealedEpochWithClaims` case, since a
+/// dispute requires at least two conflicting claims to exist.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SealedEpochState {
+    SealedEpochNoClaims { sealed_epoch: SealedEpochNoClaims },
+    SealedEpochWithClaims { claimed_epoch: EpochWithClaims },
+}
+
+impl SealedEpochState {
+    pub fn epoch_number(&self) -> U256 {
+        match self {
+            SealedEpochState::SealedEpochNoClaims { sealed_epoch } => {
+                sealed_epoch.epoch_number
+            }
+            SealedEpochState::SealedEpochWithClaims { claimed_epoch } => {
+                claimed_epoch.epoch_number
+            }
+        }
+    }
+
+    fn dapp_contract_address(&self) -> Address {
+        match self {
+            SealedEpochState::SealedEpochNoClaims { sealed_epoch } => {
+                sealed_epoch.dapp_contract_address
+            }
+            SealedEpochState::SealedEpochWithClaims { claimed_epoch } => {
+                claimed_epoch.dapp_contract_address
+            }
+        }
+    }
+
+    /// Claims submitted so far for this sealed epoch, `None` if none have
+    /// arrived yet.
+    pub fn claims(&self) -> Option<&HashMap<Address, H256>> {
+        match self {
+            SealedEpochState::SealedEpochNoClaims { .. } => None,
+            SealedEpochState::SealedEpochWithClaims { claimed_epoch } => {
+                Some(claimed_epoch.claims())
+            }
+        }
+    }
+}
+
+fn claims_by_claimant(events: Vec<ClaimFilter>) -> HashMap<Address, H256> {
+    let mut claims = HashMap::new();
+    for event in events {
+        claims.insert(event.claimant, H256::from(event.epoch_hash));
+    }
+    claims
+}
+
+fn build_state(
+    epoch_number: U256,
+    dapp_contract_address: Address,
+    claims: HashMap<Address, H256>,
+) -> SealedEpochState {
+    if claims.is_empty() {
+        SealedEpochState::SealedEpochNoClaims {
+            sealed_epoch: SealedEpochNoClaims {
+                epoch_number,
+                dapp_contract_address,
+            },
+        }
+    } else {
+        SealedEpochState::SealedEpochWithClaims {
+            claimed_epoch: EpochWithClaims::new(
+                epoch_number,
+                dapp_contract_address,
+                claims,
+            ),
+        }
+    }
+}
+
+/// Sealed-epoch StateFold Delegate: reconstructs who has claimed what for a
+/// single sealed epoch by folding the contract's per-claim events, so that
+/// `EpochState` can expose quorum progress via `consensus_status()` without
+/// re-deriving the claim map from genesis on every block.
+#[async_trait]
+impl Foldable for SealedEpochState {
+    type InitialState = (Address, U256);
+    type Error = FoldableError;
+    type UserData = ();
+
+    async fn sync<M: Middleware + 'static>(
+        initial_state: &Self::InitialState,
+        block: &Block,
+        _env: &StateFoldEnvironment<M, Self::UserData>,
+        access: Arc<SyncMiddleware<M>>,
+    ) -> Result<Self, Self::Error> {
+        let (dapp_contract_address, epoch_number) = *initial_state;
+
+        let middleware = access.get_inner();
+        let contract =
+            RollupsFacet::new(dapp_contract_address, Arc::clone(&middleware));
+
+        let claim_events = contract
+            .claim_filter()
+            .epoch_number(epoch_number)
+            .query()
+            .await
+            .context("Error querying for rollups claims")?;
+
+        Ok(build_state(
+            epoch_number,
+            dapp_contract_address,
+            claims_by_claimant(claim_events),
+        ))
+    }
+
+    async fn fold<M: Middleware + 'static>(
+        previous_state: &Self,
+        block: &Block,
+        _env: &StateFoldEnvironment<M, Self::UserData>,
+        access: Arc<FoldMiddleware<M>>,
+    ) -> Result<Self, Self::Error> {
+        let epoch_number = previous_state.epoch_number();
+        let dapp_contract_address = previous_state.dapp_contract_address();
+
+        // Check if there was (possibly) some claim emitted on this block.
+        if !(fold_utils::contains_address(
+            &block.logs_bloom,
+            &dapp_contract_address,
+        ) && fold_utils::contains_topic(
+            &block.logs_bloom,
+            &ClaimFilter::signature(),
+        )) {
+            return Ok(previous_state.clone());
+        }
+
+        let middleware = access.get_inner();
+        let contract =
+            RollupsFacet::new(dapp_contract_address, Arc::clone(&middleware));
+
+        // `access` is already scoped to this single block, so this only
+        // picks up claims submitted right here, not the whole history.
+        let claim_events = contract
+            .claim_filter()
+            .epoch_number(epoch_number)
+            .query()
+            .await
+            .context("Error querying for rollups claims")?;
+
+        if claim_events.is_empty() {
+            return Ok(previous_state.clone());
+        }
+
+        let mut claims = previous_state.claims().cloned().unwrap_or_default();
+        claims.extend(claims_by_claimant(claim_events));
+
+        Ok(build_state(epoch_number, dapp_contract_address, claims))
+    }
+}